@@ -0,0 +1,264 @@
+use pyo3::prelude::*;
+
+use notify::event::{AccessMode as NotifyAccessMode, DataChange, MetadataKind};
+
+/// Extra, backend-specific details about a raw event that don't fit the common shape.
+#[pyclass]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct EventAttributes {
+    #[pyo3(get)]
+    pub tracker: Option<usize>,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EventType {
+    Create,
+    Remove,
+    Access,
+    Modify,
+    Other,
+    Unknown,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ObjectType {
+    File,
+    Dir,
+    Other,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessType {
+    Open,
+    Read,
+    Close,
+    Other,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessMode {
+    Read,
+    Write,
+    Execute,
+    Other,
+}
+
+impl AccessMode {
+    pub fn from_raw(mode: NotifyAccessMode) -> Option<Self> {
+        match mode {
+            NotifyAccessMode::Read => Some(AccessMode::Read),
+            NotifyAccessMode::Write => Some(AccessMode::Write),
+            NotifyAccessMode::Execute => Some(AccessMode::Execute),
+            NotifyAccessMode::Other => Some(AccessMode::Other),
+            NotifyAccessMode::Any => None,
+        }
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ModifyType {
+    Other,
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MetadataType {
+    AccessTime,
+    WriteTime,
+    Permissions,
+    Ownership,
+    Extended,
+    Other,
+}
+
+impl MetadataType {
+    pub fn from_raw(kind: MetadataKind) -> Option<Self> {
+        match kind {
+            MetadataKind::AccessTime => Some(MetadataType::AccessTime),
+            MetadataKind::WriteTime => Some(MetadataType::WriteTime),
+            MetadataKind::Permissions => Some(MetadataType::Permissions),
+            MetadataKind::Ownership => Some(MetadataType::Ownership),
+            MetadataKind::Extended => Some(MetadataType::Extended),
+            MetadataKind::Other => Some(MetadataType::Other),
+            MetadataKind::Any => None,
+        }
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DataChangeType {
+    Size,
+    Content,
+    Other,
+}
+
+impl DataChangeType {
+    pub fn from_raw(change: DataChange) -> Option<Self> {
+        match change {
+            DataChange::Size => Some(DataChangeType::Size),
+            DataChange::Content => Some(DataChangeType::Content),
+            DataChange::Other => Some(DataChangeType::Other),
+            DataChange::Any => None,
+        }
+    }
+}
+
+#[pyclass(eq, eq_int)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameType {
+    From,
+    To,
+    Both,
+    Other,
+}
+
+/// A single, already-classified filesystem event handed back to Python.
+///
+/// `path` holds the event's primary path (the only path for everything but a
+/// correlated rename); `dest_path` is only set for `RenameType::Both`, where it
+/// carries the destination path paired with `path` as the source.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub(crate) struct RawEvent {
+    #[pyo3(get)]
+    pub event_type: EventType,
+    #[pyo3(get)]
+    pub object_type: Option<ObjectType>,
+    #[pyo3(get)]
+    pub access_type: Option<AccessType>,
+    #[pyo3(get)]
+    pub access_mode: Option<AccessMode>,
+    #[pyo3(get)]
+    pub modify_type: Option<ModifyType>,
+    #[pyo3(get)]
+    pub metadata_type: Option<MetadataType>,
+    #[pyo3(get)]
+    pub data_change_type: Option<DataChangeType>,
+    #[pyo3(get)]
+    pub rename_type: Option<RenameType>,
+    #[pyo3(get)]
+    pub path: String,
+    #[pyo3(get)]
+    pub dest_path: Option<String>,
+    #[pyo3(get)]
+    pub detected_at: u128,
+    #[pyo3(get)]
+    pub attrs: EventAttributes,
+}
+
+impl RawEvent {
+    fn base(event_type: EventType, detected_at: u128, path: String, attrs: EventAttributes) -> Self {
+        RawEvent {
+            event_type,
+            object_type: None,
+            access_type: None,
+            access_mode: None,
+            modify_type: None,
+            metadata_type: None,
+            data_change_type: None,
+            rename_type: None,
+            path,
+            dest_path: None,
+            detected_at,
+            attrs,
+        }
+    }
+}
+
+pub(crate) fn new_create_event(
+    object_type: Option<ObjectType>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Create, detected_at, path, attrs);
+    event.object_type = object_type;
+    event
+}
+
+pub(crate) fn new_remove_event(
+    object_type: Option<ObjectType>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Remove, detected_at, path, attrs);
+    event.object_type = object_type;
+    event
+}
+
+pub(crate) fn new_access_event(
+    access_type: Option<AccessType>,
+    access_mode: Option<AccessMode>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Access, detected_at, path, attrs);
+    event.access_type = access_type;
+    event.access_mode = access_mode;
+    event
+}
+
+pub(crate) fn new_modify_event(
+    modify_type: Option<ModifyType>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Modify, detected_at, path, attrs);
+    event.modify_type = modify_type;
+    event
+}
+
+pub(crate) fn new_modify_metadata_event(
+    metadata_type: Option<MetadataType>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Modify, detected_at, path, attrs);
+    event.metadata_type = metadata_type;
+    event
+}
+
+pub(crate) fn new_modify_data_event(
+    data_change_type: Option<DataChangeType>,
+    detected_at: u128,
+    path: String,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Modify, detected_at, path, attrs);
+    event.data_change_type = data_change_type;
+    event
+}
+
+/// Builds a rename event. `dest_path` should only be set when `rename_type` is
+/// `RenameType::Both`, i.e. when both paths are known: the `From`/`To` halves were
+/// correlated by tracker id, or the backend reported the rename with both paths.
+pub(crate) fn new_rename_event(
+    rename_type: Option<RenameType>,
+    detected_at: u128,
+    path: String,
+    dest_path: Option<String>,
+    attrs: EventAttributes,
+) -> RawEvent {
+    let mut event = RawEvent::base(EventType::Modify, detected_at, path, attrs);
+    event.rename_type = rename_type;
+    event.dest_path = dest_path;
+    event
+}
+
+pub(crate) fn new_other_event(detected_at: u128, path: String, attrs: EventAttributes) -> RawEvent {
+    RawEvent::base(EventType::Other, detected_at, path, attrs)
+}
+
+pub(crate) fn new_unknown_event(detected_at: u128, path: String, attrs: EventAttributes) -> RawEvent {
+    RawEvent::base(EventType::Unknown, detected_at, path, attrs)
+}
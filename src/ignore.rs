@@ -0,0 +1,156 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A single parsed line from a `.gitignore`-style pattern list.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: String,
+    negate: bool,
+    dir_only: bool,
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let mut pattern = line;
+
+        let negate = if let Some(stripped) = pattern.strip_prefix('!') {
+            pattern = stripped;
+            true
+        } else {
+            false
+        };
+
+        let dir_only = pattern.ends_with('/');
+        if dir_only {
+            pattern = &pattern[..pattern.len() - 1];
+        }
+
+        let anchored = pattern.starts_with('/');
+        if anchored {
+            pattern = &pattern[1..];
+        }
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(IgnoreRule {
+            pattern: pattern.to_string(),
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, relative_path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored || self.pattern.contains('/') {
+            return glob_match(&self.pattern, relative_path);
+        }
+
+        // an un-anchored, slash-free pattern may match at any depth, not just the root
+        let components: Vec<&str> = relative_path.split('/').collect();
+
+        (0..components.len()).any(|start| glob_match(&self.pattern, &components[start..].join("/")))
+    }
+}
+
+/// Matches paths against a set of gitignore-style rules, later rules overriding earlier
+/// ones and `!pattern` negating/unignoring a path an earlier rule matched.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct IgnoreMatcher {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_patterns(&mut self, patterns: &[String]) {
+        self.rules.extend(patterns.iter().filter_map(|pattern| IgnoreRule::parse(pattern)));
+    }
+
+    pub fn load_file(&mut self, path: &Path) -> io::Result<()> {
+        let contents = fs::read_to_string(path)?;
+
+        self.rules.extend(contents.lines().filter_map(IgnoreRule::parse));
+
+        Ok(())
+    }
+
+    /// `relative_path` must already be relative to the watched root the rules apply to.
+    ///
+    /// A path is ignored if it matches a rule itself, or if any of its ancestor
+    /// directories does — excluding a directory (e.g. `node_modules/`, `.git/`) must
+    /// suppress events for everything underneath it, not just the directory entry itself.
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let candidate = relative_path.to_string_lossy().replace('\\', "/");
+        let components: Vec<&str> = candidate.split('/').filter(|component| !component.is_empty()).collect();
+
+        if components.is_empty() {
+            return false;
+        }
+
+        (1..=components.len()).any(|depth| {
+            let ancestor = components[..depth].join("/");
+            // every ancestor but the path itself is necessarily a directory
+            let ancestor_is_dir = if depth == components.len() { is_dir } else { true };
+
+            self.evaluate(&ancestor, ancestor_is_dir)
+        })
+    }
+
+    fn evaluate(&self, candidate: &str, is_dir: bool) -> bool {
+        let mut ignored = false;
+
+        for rule in &self.rules {
+            if rule.matches(candidate, is_dir) {
+                ignored = !rule.negate;
+            }
+        }
+
+        ignored
+    }
+}
+
+/// Minimal glob matcher supporting `*` (any run of non-separator chars), `**` (any run of
+/// chars, including separators) and `?` (a single non-separator char).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    glob_match_bytes(pattern.as_bytes(), text.as_bytes())
+}
+
+fn glob_match_bytes(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') => {
+            if pattern.get(1) == Some(&b'*') {
+                let mut rest = &pattern[2..];
+                if rest.first() == Some(&b'/') {
+                    rest = &rest[1..];
+                }
+
+                (0..=text.len()).any(|i| glob_match_bytes(rest, &text[i..]))
+            } else {
+                let rest = &pattern[1..];
+
+                (0..=text.len())
+                    .take_while(|&i| i == 0 || text[i - 1] != b'/')
+                    .any(|i| glob_match_bytes(rest, &text[i..]))
+            }
+        }
+        Some(b'?') => !text.is_empty() && text[0] != b'/' && glob_match_bytes(&pattern[1..], &text[1..]),
+        Some(&c) => !text.is_empty() && text[0] == c && glob_match_bytes(&pattern[1..], &text[1..]),
+    }
+}
@@ -1,3 +1,5 @@
+mod events;
+mod ignore;
 mod watcher;
 
 extern crate notify;
@@ -3,13 +3,16 @@ extern crate pyo3;
 
 use pyo3::exceptions::{PyException, PyFileNotFoundError, PyOSError, PyPermissionError};
 use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
 use std::io::ErrorKind as IOErrorKind;
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
 use crossbeam_channel::{unbounded, Receiver, RecvError, RecvTimeoutError, Sender};
 use crossbeam_utils::atomic::AtomicConsume;
@@ -19,6 +22,7 @@ use crate::events::{
     new_other_event, new_remove_event, new_rename_event, new_unknown_event, AccessMode, AccessType, DataChangeType,
     EventAttributes, EventType, MetadataType, ModifyType, ObjectType, RawEvent, RenameType,
 };
+use crate::ignore::IgnoreMatcher;
 use notify::event::{
     AccessKind, CreateKind, DataChange, Event as NotifyEvent, MetadataKind, ModifyKind, RemoveKind, RenameMode,
 };
@@ -33,6 +37,11 @@ type EventSender = Sender<RawEvent>;
 type EventReceiver = Receiver<RawEvent>;
 type NotificationReceiver = Receiver<NotifyResult<NotifyEvent>>;
 
+/// How long a `MOVED_FROM` half of a rename waits for its `MOVED_TO` partner
+/// (matched by the tracker id `notify`/inotify shares between the pair) before
+/// it is given up on and emitted on its own.
+const RENAME_FLUSH_WINDOW: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 enum WatcherType {
     Poll(PollWatcher),
@@ -42,27 +51,46 @@ enum WatcherType {
 #[derive(Debug)]
 pub(crate) struct Watcher {
     debug: bool,
+    debounce_ms: u64,
+    poll_delay_ms: u64,
+    fell_back_to_poll: bool,
     notification_receiver: NotificationReceiver,
     event_receiver: EventReceiver,
     event_sender: EventSender,
     watcher: WatcherType,
     listen_thread: Option<JoinHandle<()>>,
     stop_listening: Arc<AtomicBool>,
+    watched_roots: Arc<Mutex<Vec<PathBuf>>>,
+    ignore: Arc<Mutex<IgnoreMatcher>>,
 }
 
 impl Watcher {
-    pub fn new(debug: bool, force_polling: bool, poll_delay_ms: u64) -> PyResult<Self> {
+    pub fn new(
+        debug: bool,
+        force_polling: bool,
+        poll_delay_ms: u64,
+        debounce_ms: u64,
+        compare_contents: bool,
+    ) -> PyResult<Self> {
         if force_polling {
-            return Self::new_poll_watcher(debug, poll_delay_ms);
+            return Self::new_poll_watcher(debug, poll_delay_ms, debounce_ms, compare_contents, false);
         }
 
-        return Self::new_recommended_watcher(debug, poll_delay_ms);
+        return Self::new_recommended_watcher(debug, poll_delay_ms, debounce_ms, compare_contents);
     }
 
-    fn new_poll_watcher(debug: bool, poll_delay_ms: u64) -> PyResult<Watcher> {
+    fn new_poll_watcher(
+        debug: bool,
+        poll_delay_ms: u64,
+        debounce_ms: u64,
+        compare_contents: bool,
+        fell_back_to_poll: bool,
+    ) -> PyResult<Watcher> {
         let (notification_sender, notification_receiver) = unbounded();
         let delay = Duration::from_millis(poll_delay_ms);
-        let config = NotifyConfig::default().with_poll_interval(delay);
+        let config = NotifyConfig::default()
+            .with_poll_interval(delay)
+            .with_compare_contents(compare_contents);
 
         let watcher = match PollWatcher::new(notification_sender, config) {
             Ok(watcher) => watcher,
@@ -73,16 +101,26 @@ impl Watcher {
 
         Ok(Watcher {
             debug,
+            debounce_ms,
+            poll_delay_ms,
+            fell_back_to_poll,
             notification_receiver,
             event_receiver,
             event_sender,
             watcher: WatcherType::Poll(watcher),
             listen_thread: None,
             stop_listening: Arc::new(AtomicBool::new(false)),
+            watched_roots: Arc::new(Mutex::new(Vec::new())),
+            ignore: Arc::new(Mutex::new(IgnoreMatcher::new())),
         })
     }
 
-    fn new_recommended_watcher(debug: bool, poll_delay_ms: u64) -> PyResult<Watcher> {
+    fn new_recommended_watcher(
+        debug: bool,
+        poll_delay_ms: u64,
+        debounce_ms: u64,
+        compare_contents: bool,
+    ) -> PyResult<Watcher> {
         let (notification_sender, notification_receiver) = unbounded();
 
         let watcher = match RecommendedWatcher::new(notification_sender, NotifyConfig::default()) {
@@ -100,7 +138,7 @@ impl Watcher {
                                 );
                             }
 
-                            return Self::new_poll_watcher(debug, poll_delay_ms);
+                            return Self::new_poll_watcher(debug, poll_delay_ms, debounce_ms, compare_contents, true);
                         }
 
                         Err(WatcherError::new_err(format!(
@@ -120,16 +158,43 @@ impl Watcher {
 
         Ok(Watcher {
             debug,
+            debounce_ms,
+            poll_delay_ms,
+            fell_back_to_poll: false,
             notification_receiver,
             event_receiver,
             event_sender,
             watcher: WatcherType::Recommended(watcher),
             listen_thread: None,
             stop_listening: Arc::new(AtomicBool::new(false)),
+            watched_roots: Arc::new(Mutex::new(Vec::new())),
+            ignore: Arc::new(Mutex::new(IgnoreMatcher::new())),
         })
     }
 
-    pub fn watch(&mut self, paths: Vec<String>, recursive: bool, ignore_permission_errors: bool) -> PyResult<()> {
+    /// Which backend is actually driving this watcher right now — `"Native"`, or
+    /// `"Poll(<interval_ms>ms)"` when either `force_polling` was requested or the native
+    /// backend was unavailable and we fell back.
+    pub fn active_backend(&self) -> String {
+        match self.watcher {
+            WatcherType::Recommended(_) => "Native".to_string(),
+            WatcherType::Poll(_) => format!("Poll({}ms)", self.poll_delay_ms),
+        }
+    }
+
+    /// Whether the native backend failed to initialize and we transparently fell back to
+    /// `PollWatcher`, as opposed to polling having been explicitly requested.
+    pub fn fell_back_to_poll(&self) -> bool {
+        self.fell_back_to_poll
+    }
+
+    pub fn watch(
+        &mut self,
+        paths: Vec<String>,
+        recursive: bool,
+        ignore_permission_errors: bool,
+        emit_initial: bool,
+    ) -> PyResult<()> {
         let mode = if recursive {
             RecursiveMode::Recursive
         } else {
@@ -159,6 +224,12 @@ impl Watcher {
                 }
                 _ => (),
             }
+
+            self.watched_roots.lock().unwrap().push(path.to_path_buf());
+
+            if emit_initial {
+                self.emit_initial_events(path, recursive, ignore_permission_errors)?;
+            }
         }
 
         if self.debug {
@@ -168,6 +239,129 @@ impl Watcher {
         Ok(())
     }
 
+    /// Walks `root` (respecting `recursive`) right after it's registered and pushes a
+    /// synthetic create event for every pre-existing entry, so a consumer can build a
+    /// complete picture with one code path: "tell me everything that's here now, then
+    /// keep me updated". Entries matching an active ignore rule are skipped. Unreadable
+    /// directories/entries are honored the same way `ignore_permission_errors` governs
+    /// the watch registration itself above.
+    fn emit_initial_events(&self, root: &Path, recursive: bool, ignore_permission_errors: bool) -> PyResult<()> {
+        let watched_roots = self.watched_roots.lock().unwrap().clone();
+        let ignore = self.ignore.lock().unwrap().clone();
+
+        Self::scan_dir(
+            root,
+            recursive,
+            ignore_permission_errors,
+            &watched_roots,
+            &ignore,
+            &self.event_sender,
+        )
+    }
+
+    /// Iterative (not recursive) so arbitrarily deep trees can't blow the stack.
+    fn scan_dir(
+        root: &Path,
+        recursive: bool,
+        ignore_permission_errors: bool,
+        watched_roots: &[PathBuf],
+        ignore: &IgnoreMatcher,
+        event_sender: &EventSender,
+    ) -> PyResult<()> {
+        let root_metadata = match fs::metadata(root) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                if ignore_permission_errors {
+                    return Ok(());
+                }
+                return Err(Self::map_io_error(root, err));
+            }
+        };
+
+        if !root_metadata.is_dir() {
+            // `watch()` also accepts a single file as its root; there's nothing to
+            // recurse into, just report the file itself.
+            let object_type = if root_metadata.is_file() { ObjectType::File } else { ObjectType::Other };
+
+            if let Some(path_str) = root.to_str() {
+                let attrs = EventAttributes { tracker: None };
+                let event = new_create_event(Some(object_type), Self::get_current_time_ns(), path_str.to_string(), attrs);
+
+                if !Self::is_ignored(&event, watched_roots, ignore) {
+                    event_sender.send(event).unwrap();
+                }
+            }
+
+            return Ok(());
+        }
+
+        let mut pending_dirs = vec![root.to_path_buf()];
+
+        while let Some(dir) = pending_dirs.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    if ignore_permission_errors {
+                        continue;
+                    }
+                    return Err(Self::map_io_error(&dir, err));
+                }
+            };
+
+            for entry in entries {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(err) => {
+                        if ignore_permission_errors {
+                            continue;
+                        }
+                        return Err(Self::map_io_error(&dir, err));
+                    }
+                };
+
+                let file_type = match entry.file_type() {
+                    Ok(file_type) => file_type,
+                    Err(err) => {
+                        if ignore_permission_errors {
+                            continue;
+                        }
+                        return Err(Self::map_io_error(&entry.path(), err));
+                    }
+                };
+
+                let object_type = if file_type.is_dir() {
+                    ObjectType::Dir
+                } else if file_type.is_file() {
+                    ObjectType::File
+                } else {
+                    ObjectType::Other
+                };
+
+                let path = entry.path();
+                let path_str = match path.to_str() {
+                    Some(path_str) => path_str.to_string(),
+                    None => continue,
+                };
+
+                let attrs = EventAttributes { tracker: None };
+                let event = new_create_event(Some(object_type), Self::get_current_time_ns(), path_str, attrs);
+                let ignored = Self::is_ignored(&event, watched_roots, ignore);
+
+                if !ignored {
+                    event_sender.send(event).unwrap();
+                }
+
+                // anything under an ignored directory is ignored too, so there's no point
+                // reading a potentially huge ignored subtree just to filter it away entry by entry
+                if file_type.is_dir() && recursive && !ignored {
+                    pending_dirs.push(path);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn unwatch(&mut self, paths: Vec<String>) -> PyResult<()> {
         for path_str in paths.into_iter() {
             let path = Path::new(&path_str);
@@ -183,6 +377,8 @@ impl Watcher {
                 }
                 _ => (),
             }
+
+            self.watched_roots.lock().unwrap().retain(|root| root != path);
         }
 
         if self.debug {
@@ -192,11 +388,25 @@ impl Watcher {
         Ok(())
     }
 
+    /// Adds ad-hoc gitignore-style patterns to the active filter set.
+    pub fn add_ignore(&mut self, patterns: Vec<String>) {
+        self.ignore.lock().unwrap().add_patterns(&patterns);
+    }
+
+    /// Parses a `.gitignore`-style file and merges its rules into the active filter set.
+    pub fn load_gitignore(&mut self, path: String) -> PyResult<()> {
+        self.ignore
+            .lock()
+            .unwrap()
+            .load_file(Path::new(&path))
+            .map_err(|error| WatcherError::new_err(format!("Error reading gitignore file {}: {}", path, error)))
+    }
+
     fn create_event(path: String, notification: &Event) -> RawEvent {
         let detected_at_ns = Self::get_current_time_ns();
-
-        // TODO: fill it with raw_event.attrs info
-        let attrs = EventAttributes { tracker: None };
+        let attrs = EventAttributes {
+            tracker: notification.attrs.tracker(),
+        };
 
         // TODO: find more readable way to remap event data
         return match notification.kind {
@@ -239,11 +449,19 @@ impl Watcher {
                     new_modify_data_event(DataChangeType::from_raw(data_changed), detected_at_ns, path, attrs)
                 }
                 ModifyKind::Name(rename_mode) => match rename_mode {
-                    RenameMode::From => new_rename_event(Some(RenameType::From), detected_at_ns, path, attrs),
-                    RenameMode::To => new_rename_event(Some(RenameType::To), detected_at_ns, path, attrs),
-                    RenameMode::Both => new_rename_event(Some(RenameType::Both), detected_at_ns, path, attrs), // TODO: parse the second path
-                    RenameMode::Other => new_rename_event(Some(RenameType::Other), detected_at_ns, path, attrs),
-                    RenameMode::Any => new_rename_event(None, detected_at_ns, path, attrs),
+                    RenameMode::From => new_rename_event(Some(RenameType::From), detected_at_ns, path, None, attrs),
+                    RenameMode::To => new_rename_event(Some(RenameType::To), detected_at_ns, path, None, attrs),
+                    RenameMode::Both => {
+                        let dest_path = notification
+                            .paths
+                            .get(1)
+                            .and_then(|dest| dest.to_str())
+                            .map(|dest| dest.to_string());
+
+                        new_rename_event(Some(RenameType::Both), detected_at_ns, path, dest_path, attrs)
+                    }
+                    RenameMode::Other => new_rename_event(Some(RenameType::Other), detected_at_ns, path, None, attrs),
+                    RenameMode::Any => new_rename_event(None, detected_at_ns, path, None, attrs),
                 },
                 ModifyKind::Other => new_modify_event(Some(ModifyType::Other), detected_at_ns, path, attrs),
                 ModifyKind::Any => new_modify_event(None, detected_at_ns, path, attrs),
@@ -257,17 +475,65 @@ impl Watcher {
         return Ok(self.event_receiver.recv().unwrap());
     }
 
+    /// Waits up to `timeout_ms` for the next event, returning `None` on timeout instead
+    /// of blocking forever, and raising `WatcherError` if the channel has disconnected.
+    pub fn get_timeout(&self, timeout_ms: u64) -> PyResult<Option<RawEvent>> {
+        match self.event_receiver.recv_timeout(Duration::from_millis(timeout_ms)) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(WatcherError::new_err("event channel disconnected")),
+        }
+    }
+
+    /// Waits up to `timeout_ms` for a first event, then drains up to `max - 1` more
+    /// already-buffered events without blocking further, so callers firing thousands of
+    /// events (poll watchers, recursive trees) can amortize the Python/Rust boundary.
+    pub fn get_batch(&self, max: usize, timeout_ms: u64) -> PyResult<Vec<RawEvent>> {
+        let first_event = match self.get_timeout(timeout_ms)? {
+            Some(event) => event,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut batch = Vec::with_capacity(max.max(1));
+        batch.push(first_event);
+
+        while batch.len() < max {
+            match self.event_receiver.try_recv() {
+                Ok(event) => batch.push(event),
+                Err(_) => break,
+            }
+        }
+
+        Ok(batch)
+    }
+
     pub fn start(&mut self) {
         let notification_receiver = self.notification_receiver.clone();
         let event_sender = self.event_sender.clone();
         let stop_listening = self.stop_listening.clone();
         let debug = self.debug;
+        let debounce_ms = self.debounce_ms;
+        let watched_roots = self.watched_roots.clone();
+        let ignore = self.ignore.clone();
 
         let listen_thread = std::thread::spawn(move || {
+            // Tracker id -> (buffered MOVED_FROM path, time it was buffered), waiting for
+            // the matching MOVED_TO to arrive so the two halves can be merged into one event.
+            let mut pending_renames: HashMap<usize, (PathBuf, Instant)> = HashMap::new();
+            // Path -> (coalesced event, time it last changed), waiting for `debounce_ms` of
+            // quiet on that path before the merged event is handed to `event_sender`.
+            let mut pending_debounced: HashMap<String, (RawEvent, Instant)> = HashMap::new();
+            let default_timeout = Duration::from_millis(400);
+
             while !stop_listening.load(Ordering::Relaxed) {
-                let timeout = Duration::from_millis(400);
+                let timeout = Self::next_wake_up(&pending_debounced, &pending_renames, debounce_ms, default_timeout);
                 let timed_out_result = &notification_receiver.recv_timeout(timeout);
 
+                // one snapshot per iteration, shared by the notification handling below and
+                // the flush calls at the end of the loop, instead of a clone for each
+                let watched_roots_snapshot = watched_roots.lock().unwrap().clone();
+                let ignore_snapshot = ignore.lock().unwrap().clone();
+
                 match timed_out_result {
                     Ok(notification_result) => match notification_result {
                         Ok(notification) => {
@@ -283,9 +549,17 @@ impl Watcher {
                                     }
                                 };
 
-                                let raw_event = Self::create_event(path, notification);
-
-                                event_sender.send(raw_event).unwrap();
+                                Self::handle_notification(
+                                    notification,
+                                    path,
+                                    path_buf.clone(),
+                                    &mut pending_renames,
+                                    debounce_ms,
+                                    &mut pending_debounced,
+                                    &watched_roots_snapshot,
+                                    &ignore_snapshot,
+                                    &event_sender,
+                                );
                             }
                         }
                         Err(e) => {
@@ -299,12 +573,241 @@ impl Watcher {
                         }
                     },
                 };
+
+                Self::flush_expired_renames(
+                    &mut pending_renames,
+                    RENAME_FLUSH_WINDOW,
+                    debounce_ms,
+                    &mut pending_debounced,
+                    &watched_roots_snapshot,
+                    &ignore_snapshot,
+                    &event_sender,
+                );
+                Self::flush_expired_debounced(&mut pending_debounced, debounce_ms, &event_sender);
             }
+
+            let watched_roots_snapshot = watched_roots.lock().unwrap().clone();
+            let ignore_snapshot = ignore.lock().unwrap().clone();
+
+            // on stop, every still-unpaired half is emitted on its own, however recently it was buffered
+            Self::flush_expired_renames(
+                &mut pending_renames,
+                Duration::ZERO,
+                debounce_ms,
+                &mut pending_debounced,
+                &watched_roots_snapshot,
+                &ignore_snapshot,
+                &event_sender,
+            );
+            Self::flush_all_debounced(&mut pending_debounced, &event_sender);
         });
 
         self.listen_thread = Some(listen_thread)
     }
 
+    fn handle_notification(
+        notification: &NotifyEvent,
+        path: String,
+        path_buf: PathBuf,
+        pending_renames: &mut HashMap<usize, (PathBuf, Instant)>,
+        debounce_ms: u64,
+        pending_debounced: &mut HashMap<String, (RawEvent, Instant)>,
+        watched_roots: &[PathBuf],
+        ignore: &IgnoreMatcher,
+        event_sender: &EventSender,
+    ) {
+        if let EventKind::Modify(ModifyKind::Name(rename_mode)) = notification.kind {
+            let tracker = notification.attrs.tracker();
+
+            match (rename_mode, tracker) {
+                (RenameMode::From, Some(tracker_id)) => {
+                    pending_renames.insert(tracker_id, (path_buf, Instant::now()));
+                    return;
+                }
+                (RenameMode::To, Some(tracker_id)) => {
+                    if let Some((from_path, _)) = pending_renames.remove(&tracker_id) {
+                        if let Some(src_path) = from_path.to_str() {
+                            let attrs = EventAttributes {
+                                tracker: Some(tracker_id),
+                            };
+                            let combined = new_rename_event(
+                                Some(RenameType::Both),
+                                Self::get_current_time_ns(),
+                                src_path.to_string(),
+                                Some(path),
+                                attrs,
+                            );
+
+                            Self::dispatch_event(combined, debounce_ms, pending_debounced, watched_roots, ignore, event_sender);
+                            return;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let raw_event = Self::create_event(path, notification);
+
+        Self::dispatch_event(raw_event, debounce_ms, pending_debounced, watched_roots, ignore, event_sender);
+    }
+
+    fn flush_expired_renames(
+        pending_renames: &mut HashMap<usize, (PathBuf, Instant)>,
+        flush_window: Duration,
+        debounce_ms: u64,
+        pending_debounced: &mut HashMap<String, (RawEvent, Instant)>,
+        watched_roots: &[PathBuf],
+        ignore: &IgnoreMatcher,
+        event_sender: &EventSender,
+    ) {
+        // collect the expired halves first and dispatch them once `retain` has finished
+        let mut expired = Vec::new();
+
+        pending_renames.retain(|tracker_id, (from_path, buffered_at)| {
+            if buffered_at.elapsed() < flush_window {
+                return true;
+            }
+
+            expired.push((*tracker_id, from_path.clone()));
+
+            false
+        });
+
+        for (tracker_id, from_path) in expired {
+            if let Some(path) = from_path.to_str() {
+                let attrs = EventAttributes {
+                    tracker: Some(tracker_id),
+                };
+                let event = new_rename_event(
+                    Some(RenameType::From),
+                    Self::get_current_time_ns(),
+                    path.to_string(),
+                    None,
+                    attrs,
+                );
+
+                Self::dispatch_event(event, debounce_ms, pending_debounced, watched_roots, ignore, event_sender);
+            }
+        }
+    }
+
+    /// Resolves `event`'s path relative to whichever watched root contains it and checks
+    /// it against the active ignore rules.
+    fn is_ignored(event: &RawEvent, watched_roots: &[PathBuf], ignore: &IgnoreMatcher) -> bool {
+        let path = Path::new(&event.path);
+
+        let relative_path = watched_roots
+            .iter()
+            .find_map(|root| path.strip_prefix(root).ok())
+            .unwrap_or(path);
+
+        let is_dir = matches!(event.object_type, Some(ObjectType::Dir));
+
+        ignore.is_ignored(relative_path, is_dir)
+    }
+
+    /// Sends `event` straight through when debouncing is off, otherwise merges it into
+    /// the pending-per-path map to be flushed once that path has been quiet long enough.
+    /// Either way, an event matching an active ignore rule is dropped before it reaches
+    /// `event_sender`.
+    fn dispatch_event(
+        event: RawEvent,
+        debounce_ms: u64,
+        pending_debounced: &mut HashMap<String, (RawEvent, Instant)>,
+        watched_roots: &[PathBuf],
+        ignore: &IgnoreMatcher,
+        event_sender: &EventSender,
+    ) {
+        if Self::is_ignored(&event, watched_roots, ignore) {
+            return;
+        }
+
+        if debounce_ms == 0 {
+            event_sender.send(event).unwrap();
+            return;
+        }
+
+        match pending_debounced.get_mut(&event.path) {
+            Some((pending_event, last_changed)) => {
+                if event.event_type == EventType::Remove && pending_event.event_type == EventType::Create {
+                    // the file never really existed as far as a debounced observer is concerned
+                    pending_debounced.remove(&event.path);
+                    return;
+                }
+
+                if event.event_type != EventType::Modify || pending_event.event_type != EventType::Create {
+                    *pending_event = event;
+                }
+
+                *last_changed = Instant::now();
+            }
+            None => {
+                let path = event.path.clone();
+                pending_debounced.insert(path, (event, Instant::now()));
+            }
+        }
+    }
+
+    fn flush_expired_debounced(
+        pending_debounced: &mut HashMap<String, (RawEvent, Instant)>,
+        debounce_ms: u64,
+        event_sender: &EventSender,
+    ) {
+        if debounce_ms == 0 {
+            return;
+        }
+
+        let debounce_window = Duration::from_millis(debounce_ms);
+
+        pending_debounced.retain(|_, (event, last_changed)| {
+            if last_changed.elapsed() < debounce_window {
+                return true;
+            }
+
+            event_sender.send(event.clone()).unwrap();
+
+            false
+        });
+    }
+
+    fn flush_all_debounced(pending_debounced: &mut HashMap<String, (RawEvent, Instant)>, event_sender: &EventSender) {
+        for (_, (event, _)) in pending_debounced.drain() {
+            event_sender.send(event).unwrap();
+        }
+    }
+
+    /// How long the listen loop may block before it next needs to check on a debounced
+    /// path or a buffered rename half, capped at `default_timeout`.
+    fn next_wake_up(
+        pending_debounced: &HashMap<String, (RawEvent, Instant)>,
+        pending_renames: &HashMap<usize, (PathBuf, Instant)>,
+        debounce_ms: u64,
+        default_timeout: Duration,
+    ) -> Duration {
+        let nearest_rename_expiry = pending_renames
+            .values()
+            .map(|(_, buffered_at)| RENAME_FLUSH_WINDOW.saturating_sub(buffered_at.elapsed()))
+            .min();
+
+        let nearest_debounce_expiry = if debounce_ms == 0 {
+            None
+        } else {
+            let debounce_window = Duration::from_millis(debounce_ms);
+
+            pending_debounced
+                .values()
+                .map(|(_, last_changed)| debounce_window.saturating_sub(last_changed.elapsed()))
+                .min()
+        };
+
+        [nearest_rename_expiry, nearest_debounce_expiry, Some(default_timeout)]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(default_timeout)
+    }
+
     pub fn stop(&mut self) {
         if let Some(listen_thread) = self.listen_thread.take() {
             self.stop_listening.store(true, Ordering::Relaxed);
@@ -346,4 +849,14 @@ impl Watcher {
 
         PyOSError::new_err(format!("{} ({:?})", err_str, notify_error))
     }
+
+    fn map_io_error(path: &Path, io_error: io::Error) -> PyErr {
+        let err_str = format!("{}: {}", path.display(), io_error);
+
+        match io_error.kind() {
+            IOErrorKind::NotFound => PyFileNotFoundError::new_err(err_str),
+            IOErrorKind::PermissionDenied => PyPermissionError::new_err(err_str),
+            _ => PyOSError::new_err(err_str),
+        }
+    }
 }